@@ -9,12 +9,22 @@ use async_std::{
 use chrono::{DateTime, Duration, Local};
 use sorted_vec::SortedVec;
 use std::cmp::Ordering;
+use uuid::Uuid;
+
+// local crate imports.
+use crate::task::{Task, TaskImportance};
 
 /// docs
 #[derive(Eq, PartialEq, Debug)]
 pub struct Alarm {
     name: String,
     time: DateTime<Local>,
+    /// Id of the task this alarm was scheduled from, so it can be cancelled
+    /// when the task is completed or its deadline changes.
+    task_id: Uuid,
+    /// Importance of the originating task, so channel consumers can decide how
+    /// aggressively to nag.
+    importance: TaskImportance,
 }
 
 impl PartialOrd for Alarm {
@@ -29,6 +39,26 @@ impl Ord for Alarm {
 }
 
 impl Alarm {
+    /// Build an alarm tied to an originating task.
+    pub fn new(name: String, time: DateTime<Local>, task_id: Uuid, importance: TaskImportance) -> Self {
+        Self {
+            name,
+            time,
+            task_id,
+            importance,
+        }
+    }
+
+    /// Id of the task this alarm fires for.
+    pub fn task_id(&self) -> Uuid {
+        self.task_id
+    }
+
+    /// Importance of the originating task.
+    pub fn importance(&self) -> TaskImportance {
+        self.importance
+    }
+
     pub async fn activate(self) -> Self {
         let sleep_time = self.time - Local::now();
         task::sleep(sleep_time.to_std().unwrap()).await;
@@ -57,6 +87,51 @@ impl Nagger {
         self.alarms.lock().await.insert(alarm);
     }
 
+    /// # Summary
+    /// Walk a task tree and schedule an alarm for every reminder and/or
+    /// deadline still in the future, tagging each with the task's id and
+    /// importance.
+    ///
+    /// # Parameters
+    /// * task: root of the tree to schedule; subtasks are walked recursively.
+    pub async fn schedule_task(&self, task: &Task) {
+        let now = Local::now();
+        if let Some(reminder) = task.reminder() {
+            if reminder > now {
+                self.add_alarm(Alarm::new(
+                    format!("{} (reminder)", task.name()),
+                    reminder,
+                    task.id(),
+                    task.importance(),
+                ))
+                .await;
+            }
+        }
+        if let Some(deadline) = task.deadline() {
+            if deadline > now {
+                self.add_alarm(Alarm::new(
+                    format!("{} (deadline)", task.name()),
+                    deadline,
+                    task.id(),
+                    task.importance(),
+                ))
+                .await;
+            }
+        }
+        for subtask in task.subtasks_iter() {
+            // box the recursive future so the async fn stays Sized.
+            Box::pin(self.schedule_task(subtask)).await;
+        }
+    }
+
+    /// delete every alarm scheduled from a given task id.
+    pub async fn del_task_alarms(&self, task_id: Uuid) {
+        let mut lock = self.alarms.lock().await;
+        while let Some(pos) = lock.iter().position(|alarm| alarm.task_id == task_id) {
+            lock.remove_index(pos);
+        }
+    }
+
     /// delete alarm from the queue.
     pub async fn del_alarm(&self, name: &str) -> Option<Alarm> {
         let mut lock = self.alarms.lock().await;
@@ -1,6 +1,10 @@
 //! module containing the builder for our task struct.
 
-use chrono::{DateTime, Local};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Weekday,
+};
+use std::collections::HashSet;
+use uuid::Uuid;
 
 //local import
 use super::{TaskImportance, TaskStatus};
@@ -11,10 +15,19 @@ pub struct TaskBuilder {
     pub name: Option<String>,
     /// Optional deadline for the task.
     pub deadline: Option<DateTime<Local>>,
+    /// Error left behind by a failed [`TaskBuilder::deadline_str`] so it can be
+    /// surfaced at construction rather than silently dropped.
+    pub deadline_err: Option<String>,
+    /// Optional reminder time for the task, distinct from the deadline.
+    pub reminder: Option<DateTime<Local>>,
     /// Optional importance for the task.
     pub importance: Option<TaskImportance>,
     /// Optional status for the task.
     pub status: Option<TaskStatus>,
+    /// Tags to organize the task by context.
+    pub tags: HashSet<String>,
+    /// Ids of tasks that must be completed before this one.
+    pub dependencies: HashSet<Uuid>,
     /// Optional subtask list for the task.
     pub subtasks: Vec<TaskBuilder>,
 }
@@ -25,8 +38,12 @@ impl TaskBuilder {
         Self {
             name: None,
             deadline: None,
+            deadline_err: None,
+            reminder: None,
             importance: None,
             status: None,
+            tags: HashSet::new(),
+            dependencies: HashSet::new(),
             subtasks: Vec::new(),
         }
     }
@@ -47,6 +64,32 @@ impl TaskBuilder {
         }
     }
 
+    /// # Summary
+    /// Set the deadline from fuzzy human input like `"tomorrow 5pm"`,
+    /// `"next friday"`, or `"in 3 days"`.
+    ///
+    /// Strict `%F` (date-only, 00:00) and `%F %H:%M` formats are tried first,
+    /// then relative/keyword parsing. A parse failure is stored and surfaced by
+    /// [`Task::new`](super::Task::new) rather than silently dropped.
+    pub fn deadline_str(mut self, val: &str) -> Self {
+        match parse_deadline(val) {
+            Ok(dt) => {
+                self.deadline = Some(dt);
+                self.deadline_err = None;
+            }
+            Err(msg) => self.deadline_err = Some(msg),
+        }
+        self
+    }
+
+    /// Set the reminder time.
+    pub fn reminder(self, val: DateTime<Local>) -> Self {
+        Self {
+            reminder: Some(val),
+            ..self
+        }
+    }
+
     /// Set the importance.
     pub fn importance(self, val: TaskImportance) -> Self {
         Self {
@@ -63,9 +106,148 @@ impl TaskBuilder {
         }
     }
 
+    /// Add a single tag.
+    pub fn tag(mut self, val: &str) -> Self {
+        self.tags.insert(val.to_string());
+        self
+    }
+
+    /// Add several tags at once.
+    pub fn add_tags<I, S>(mut self, vals: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.tags
+            .extend(vals.into_iter().map(|val| val.as_ref().to_string()));
+        self
+    }
+
+    /// Mark this task as depending on another task's id.
+    pub fn depends_on(mut self, val: Uuid) -> Self {
+        self.dependencies.insert(val);
+        self
+    }
+
     /// Add a subtask builder.
     pub fn add_subtask(mut self, val: TaskBuilder) -> Self {
         self.subtasks.push(val);
         self
     }
 }
+
+/// Resolve fuzzy human deadline input to a concrete local timestamp.
+///
+/// Strict formats win first, then the relative/keyword grammar described on
+/// [`TaskBuilder::deadline_str`].
+fn parse_deadline(input: &str) -> Result<DateTime<Local>, String> {
+    let trimmed = input.trim();
+    // strict formats first: full timestamp, then a bare date at midnight.
+    if let Ok(ndt) = NaiveDateTime::parse_from_str(trimmed, "%F %H:%M") {
+        return local_from_naive(ndt);
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(trimmed, "%F") {
+        return local_from_naive(date.and_hms_opt(0, 0, 0).unwrap());
+    }
+
+    let lower = trimmed.to_lowercase();
+    let now = Local::now();
+
+    // "in N days" / "in N hours".
+    if let Some(rest) = lower.strip_prefix("in ") {
+        let mut parts = rest.split_whitespace();
+        let amount = parts
+            .next()
+            .and_then(|s| s.parse::<i64>().ok())
+            .ok_or_else(|| format!("could not parse amount in '{}'", input))?;
+        return match parts.next() {
+            Some("day") | Some("days") => Ok(now + Duration::days(amount)),
+            Some("hour") | Some("hours") => Ok(now + Duration::hours(amount)),
+            other => Err(format!(
+                "unknown relative unit {} in '{}'",
+                other.unwrap_or("<missing>"),
+                input
+            )),
+        };
+    }
+
+    // keyword day with an optional trailing time.
+    let mut words = lower.split_whitespace();
+    let first = words
+        .next()
+        .ok_or_else(|| format!("empty deadline '{}'", input))?;
+    let today = now.date_naive();
+    let (date, time_words): (NaiveDate, Vec<&str>) = match first {
+        "today" => (today, words.collect()),
+        "tomorrow" => (today + Duration::days(1), words.collect()),
+        "next" => {
+            let weekday = words
+                .next()
+                .and_then(parse_weekday)
+                .ok_or_else(|| format!("expected a weekday after 'next' in '{}'", input))?;
+            (next_weekday(today, weekday), words.collect())
+        }
+        _ => return Err(format!("could not parse deadline '{}'", input)),
+    };
+
+    let time = match time_words.as_slice() {
+        [] => NaiveTime::from_hms_opt(0, 0, 0).unwrap(),
+        [t] => parse_time(t).ok_or_else(|| format!("could not parse time '{}'", t))?,
+        _ => return Err(format!("unexpected trailing input in '{}'", input)),
+    };
+    local_from_naive(date.and_time(time))
+}
+
+/// Attach the local offset to a naive timestamp, picking the earlier instant
+/// across a DST fold and rejecting times that do not exist locally.
+fn local_from_naive(ndt: NaiveDateTime) -> Result<DateTime<Local>, String> {
+    match Local.from_local_datetime(&ndt) {
+        chrono::LocalResult::Single(dt) => Ok(dt),
+        chrono::LocalResult::Ambiguous(dt, _) => Ok(dt),
+        chrono::LocalResult::None => Err(format!("{} does not exist in the local timezone", ndt)),
+    }
+}
+
+/// Map a lowercase weekday name or three-letter abbreviation to a [`Weekday`].
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// First date strictly after `from` that lands on `target`.
+fn next_weekday(from: NaiveDate, target: Weekday) -> NaiveDate {
+    let mut date = from + Duration::days(1);
+    while date.weekday() != target {
+        date += Duration::days(1);
+    }
+    date
+}
+
+/// Parse a clock time in 24h (`17:00`) or 12h (`5pm`, `5:30pm`) form.
+fn parse_time(word: &str) -> Option<NaiveTime> {
+    if let Ok(t) = NaiveTime::parse_from_str(word, "%H:%M") {
+        return Some(t);
+    }
+    let upper = word.to_uppercase();
+    if let Ok(t) = NaiveTime::parse_from_str(&upper, "%I:%M%p") {
+        return Some(t);
+    }
+    // `%I%p` alone needs a minute component to build a complete NaiveTime, so
+    // a bare hour+meridiem like "5pm" is rewritten with an explicit ":00".
+    if let Some(meridiem) = upper.strip_suffix("AM").or_else(|| upper.strip_suffix("PM")) {
+        let suffix = &upper[meridiem.len()..];
+        if let Ok(t) = NaiveTime::parse_from_str(&format!("{}:00{}", meridiem, suffix), "%I:%M%p")
+        {
+            return Some(t);
+        }
+    }
+    None
+}
@@ -6,31 +6,51 @@
 // External imports.
 use chrono::prelude::*;
 use chrono::Duration;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 // std lib imports.
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::fmt;
+use std::fs;
+use std::path::Path;
 use std::slice::{Iter, IterMut};
 
 // modules
 pub mod builder;
 pub mod progress;
+pub(crate) mod serde_helpers;
+pub mod undo;
 
 // local crate imports;
 pub use builder::TaskBuilder;
-use progress::{Completion, ProgressNote};
+use progress::{Completion, ProgressNote, TimeEntry};
 
 /// Main struct of the code, defines how we understand Tasks.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
+    #[serde(with = "serde_helpers::uuid")]
     id: Uuid,
     name: String,
+    #[serde(default, with = "serde_helpers::datetime::option")]
     deadline: Option<DateTime<Local>>,
+    #[serde(default, with = "serde_helpers::datetime::option")]
+    reminder: Option<DateTime<Local>>,
     importance: TaskImportance,
     status: TaskStatus,
+    #[serde(default)]
+    tags: HashSet<String>,
+    #[serde(default, with = "serde_helpers::uuid_set")]
+    dependencies: HashSet<Uuid>,
     subtasks: Vec<Task>,
     notes: Vec<ProgressNote>,
+    #[serde(default)]
+    time_entries: Vec<TimeEntry>,
+    /// Error left behind by a failed [`TaskBuilder::deadline_str`], surfaced
+    /// here instead of being dropped silently. Not persisted.
+    #[serde(skip)]
+    deadline_err: Option<String>,
 }
 
 impl Default for Task {
@@ -51,14 +71,21 @@ impl Task {
             id: Uuid::new_v4(),
             name: builder.name.unwrap_or(String::from("new task...")),
             deadline: builder.deadline,
+            reminder: builder.reminder,
             importance: builder.importance.unwrap_or(TaskImportance::Normal),
             status: builder.status.unwrap_or(TaskStatus::InProgress),
+            tags: builder.tags.drain().collect(),
+            dependencies: builder.dependencies.drain().collect(),
             subtasks: builder
                 .subtasks
                 .drain(..)
                 .map(|subtask_builder| Self::new(subtask_builder))
                 .collect(),
             notes: Vec::new(),
+            time_entries: Vec::new(),
+            // surface a failed fuzzy deadline parse rather than dropping it
+            // silently; the caller can check `deadline_error()`.
+            deadline_err: builder.deadline_err.take(),
         }
     }
 
@@ -75,6 +102,15 @@ impl Task {
     pub fn deadline(&self) -> Option<DateTime<Local>> {
         self.deadline
     }
+    /// Get the error left behind by a failed [`TaskBuilder::deadline_str`]
+    /// parse, if any.
+    pub fn deadline_error(&self) -> Option<&str> {
+        self.deadline_err.as_deref()
+    }
+    /// Get the reminder time of a task.
+    pub fn reminder(&self) -> Option<DateTime<Local>> {
+        self.reminder
+    }
     /// Get the importance of a task.
     pub fn importance(&self) -> TaskImportance {
         self.importance
@@ -83,6 +119,92 @@ impl Task {
     pub fn status(&self) -> TaskStatus {
         self.status
     }
+    /// Get the tags of a task.
+    pub fn tags(&self) -> &HashSet<String> {
+        &self.tags
+    }
+
+    /// Add a tag to the task, returning whether it was newly inserted.
+    pub fn add_tag(&mut self, tag: &str) -> bool {
+        self.tags.insert(tag.to_string())
+    }
+
+    /// Remove a tag from the task, returning whether it was present.
+    pub fn remove_tag(&mut self, tag: &str) -> bool {
+        self.tags.remove(tag)
+    }
+
+    /// Get the dependencies of a task.
+    pub fn dependencies(&self) -> &HashSet<Uuid> {
+        &self.dependencies
+    }
+
+    /// Build an adjacency map of id => dependency ids over the whole subtree.
+    pub fn dependency_graph(&self) -> HashMap<Uuid, HashSet<Uuid>> {
+        let mut graph = HashMap::new();
+        self.collect_dependencies(&mut graph);
+        graph
+    }
+
+    fn collect_dependencies(&self, graph: &mut HashMap<Uuid, HashSet<Uuid>>) {
+        graph.insert(self.id, self.dependencies.clone());
+        for subtask in self.subtasks.iter() {
+            subtask.collect_dependencies(graph);
+        }
+    }
+
+    /// # Summary
+    /// Add `dep` as a prerequisite of this task, refusing any edge that would
+    /// introduce a cycle.
+    ///
+    /// # Parameters
+    /// * dep: id of the prospective prerequisite task.
+    /// * graph: adjacency map for the tree, e.g. from [`Task::dependency_graph`].
+    ///
+    /// # Return Val
+    /// `Ok(true)` if the edge was newly added, `Ok(false)` if it was already
+    /// present, and [`TaskError::DependencyCycle`] if it would form a cycle.
+    ///
+    /// # Caveat
+    /// `graph` is only as fresh as whenever the caller built it: this check
+    /// cannot see edges added by an earlier call in the same batch, so
+    /// chaining several `add_dependency` calls against one stale `graph` can
+    /// still build a cycle. Recompute [`Task::dependency_graph`] between
+    /// calls when adding more than one edge.
+    pub fn add_dependency(
+        &mut self,
+        dep: Uuid,
+        graph: &HashMap<Uuid, HashSet<Uuid>>,
+    ) -> Result<bool, Box<dyn Error>> {
+        // walk the existing edges from the prospective dependency; if our own
+        // id is reachable then depending on it would close a loop.
+        let mut stack = vec![dep];
+        let mut seen = HashSet::new();
+        while let Some(current) = stack.pop() {
+            if current == self.id {
+                return Err(Box::new(TaskError::DependencyCycle(self.id, dep)));
+            }
+            if !seen.insert(current) {
+                continue;
+            }
+            if let Some(edges) = graph.get(&current) {
+                stack.extend(edges.iter().copied());
+            }
+        }
+        Ok(self.dependencies.insert(dep))
+    }
+
+    /// Collect every task in the subtree (self included) carrying `tag`.
+    pub fn find_by_tag(&self, tag: &str) -> Vec<&Task> {
+        let mut found = Vec::new();
+        if self.tags.contains(tag) {
+            found.push(self);
+        }
+        for subtask in self.subtasks.iter() {
+            found.extend(subtask.find_by_tag(tag));
+        }
+        found
+    }
 
     /// Get the percentage of Task that is completed.
     pub fn completion(&self) -> Completion {
@@ -103,6 +225,37 @@ impl Task {
         }
     }
 
+    /// Get the completion weighted by subtask importance, using the default
+    /// weights (Casual=1, Normal=2, Important=3, Critical=5).
+    pub fn completion_weighted(&self) -> Completion {
+        self.completion_weighted_with(&default_importance_weight)
+    }
+
+    /// As [`Task::completion_weighted`], but with a caller-supplied weighting
+    /// scheme so the importance weights can be tuned.
+    pub fn completion_weighted_with<F>(&self, weight: &F) -> Completion
+    where
+        F: Fn(TaskImportance) -> i32,
+    {
+        if self.status == TaskStatus::Completed {
+            return Completion::full();
+        }
+        // the notes-only value is weighted by this task's own importance,
+        // alongside each subtask weighted by its importance.
+        let mut total = weight(self.importance) as i64;
+        let mut sum = (weight(self.importance) * self.completion_notes_only().val()) as i64;
+        for subtask in self.subtasks.iter() {
+            let w = weight(subtask.importance);
+            total += w as i64;
+            sum += (w * subtask.completion_weighted_with(weight).val()) as i64;
+        }
+        if total == 0 {
+            Completion::zero()
+        } else {
+            Completion::new((sum / total) as i32)
+        }
+    }
+
     fn completion_notes_only(&self) -> Completion {
         // get the completion markers from the notes and sum them.
         self.notes.iter().fold(Completion::zero(), |acc, note| {
@@ -181,24 +334,83 @@ impl Task {
     }
 
     /// Function to mark complete a currently active task.
+    /// Fails if any dependency in the tree is not yet completed, so call this
+    /// on the root of the tree the dependencies are expressed over.
     pub fn complete(&mut self) -> Result<(), Box<dyn Error>> {
-        if self.status != TaskStatus::Completed {
-            for task in self
-                .subtasks
-                .iter_mut()
-                // this makes sure that only the first explicit call of resume checks for
-                // completed.
-                .filter(|task| task.status != TaskStatus::Completed)
-            {
-                task.complete()?;
-            }
-            self.status = TaskStatus::Completed;
-            Ok(())
-        } else {
-            Err(Box::new(TaskError::TaskStatusError(format!(
+        let id = self.id;
+        self.complete_task(id).expect("self is always found by its own id")
+    }
+
+    /// Complete the task `id` somewhere in this tree, validating dependencies
+    /// against the whole tree's status snapshot rather than just `id`'s own
+    /// subtree, since a dependency may point at a sibling branch. Returns
+    /// `None` if `id` is not present in this tree.
+    pub(crate) fn complete_task(&mut self, id: Uuid) -> Option<Result<(), Box<dyn Error>>> {
+        let mut statuses = HashMap::new();
+        self.collect_statuses(&mut statuses);
+        let task = self.find_task_mut(id)?;
+        Some(task.complete_checked(&statuses))
+    }
+
+    /// Body of [`Task::complete`], gated against a status snapshot of the
+    /// whole tree taken before any task was flipped to completed. The subtree
+    /// is walked by [`Task::validate_complete`] and [`Task::apply_complete`].
+    ///
+    /// Validates every dependency in the subtree before mutating anything,
+    /// so a failure partway down does not leave already-checked siblings
+    /// stuck as `Completed` while the overall call reports `Err`.
+    fn complete_checked(
+        &mut self,
+        statuses: &HashMap<Uuid, TaskStatus>,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.status == TaskStatus::Completed {
+            return Err(Box::new(TaskError::TaskStatusError(format!(
                 "Task {} is already complete!",
                 self.name
-            ))))
+            ))));
+        }
+        self.validate_complete(statuses)?;
+        self.apply_complete();
+        Ok(())
+    }
+
+    /// Read-only pass of [`Task::complete_checked`]: check this task's and
+    /// every not-yet-completed subtask's dependencies without mutating
+    /// status.
+    fn validate_complete(&self, statuses: &HashMap<Uuid, TaskStatus>) -> Result<(), Box<dyn Error>> {
+        for dep in self.dependencies.iter() {
+            if statuses.get(dep) != Some(&TaskStatus::Completed) {
+                return Err(Box::new(TaskError::UnmetDependency(self.id, *dep)));
+            }
+        }
+        for task in self
+            .subtasks
+            .iter()
+            .filter(|task| task.status != TaskStatus::Completed)
+        {
+            task.validate_complete(statuses)?;
+        }
+        Ok(())
+    }
+
+    /// Mutating pass of [`Task::complete_checked`], only ever run once
+    /// [`Task::validate_complete`] has confirmed the whole subtree is clear.
+    fn apply_complete(&mut self) {
+        for task in self
+            .subtasks
+            .iter_mut()
+            .filter(|task| task.status != TaskStatus::Completed)
+        {
+            task.apply_complete();
+        }
+        self.status = TaskStatus::Completed;
+    }
+
+    /// Collect the status of every task in the subtree, keyed by id.
+    fn collect_statuses(&self, map: &mut HashMap<Uuid, TaskStatus>) {
+        map.insert(self.id, self.status);
+        for subtask in self.subtasks.iter() {
+            subtask.collect_statuses(map);
         }
     }
 
@@ -279,10 +491,108 @@ impl Task {
             None => ProgressNote::new(note),
         });
     }
+
+    /// Remove and return the most recently added note, if any.
+    pub fn remove_last_note(&mut self) -> Option<ProgressNote> {
+        self.notes.pop()
+    }
+
+    /// Find a task by id anywhere in the subtree (self included), mutably.
+    pub(crate) fn find_task_mut(&mut self, id: Uuid) -> Option<&mut Task> {
+        if self.id == id {
+            return Some(self);
+        }
+        for subtask in self.subtasks.iter_mut() {
+            if let Some(found) = subtask.find_task_mut(id) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// Log a block of time against this task.
+    pub fn log_time(&mut self, duration: Duration, note: Option<String>) {
+        self.time_entries.push(TimeEntry::new(duration, note));
+    }
+
+    /// Total time logged against this task plus all of its subtasks.
+    pub fn total_logged(&self) -> Duration {
+        self.subtasks.iter().fold(
+            self.time_entries
+                .iter()
+                .fold(Duration::zero(), |acc, entry| acc + entry.duration),
+            |acc, subtask| acc + subtask.total_logged(),
+        )
+    }
+
+    /// Roll up logged time by day over this task and all subtasks, sorted by
+    /// date.
+    pub fn logged_by_day(&self) -> Vec<(NaiveDate, Duration)> {
+        let mut totals: HashMap<NaiveDate, Duration> = HashMap::new();
+        self.collect_logged_by_day(&mut totals);
+        let mut rollup: Vec<(NaiveDate, Duration)> = totals.into_iter().collect();
+        rollup.sort_by_key(|(date, _)| *date);
+        rollup
+    }
+
+    fn collect_logged_by_day(&self, totals: &mut HashMap<NaiveDate, Duration>) {
+        for entry in self.time_entries.iter() {
+            let acc = totals
+                .entry(entry.logged_date)
+                .or_insert_with(Duration::zero);
+            *acc += entry.duration;
+        }
+        for subtask in self.subtasks.iter() {
+            subtask.collect_logged_by_day(totals);
+        }
+    }
+
+    /// # Summary
+    /// Save the full task tree to disk, serializing the recursive
+    /// subtask/notes structure.
+    ///
+    /// # Parameters
+    /// * path: backend is picked from the extension, `.toml` gives TOML and
+    ///   anything else (e.g. `.json`) gives JSON.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
+        let contents = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::to_string_pretty(self)?,
+            _ => serde_json::to_string_pretty(self)?,
+        };
+        fs::write(path, contents)?;
+        Ok(())
+    }
+
+    /// # Summary
+    /// Reload a task tree previously written with [`Task::save`].
+    ///
+    /// # Parameters
+    /// * path: backend is picked from the extension, matching [`Task::save`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)?;
+        let task = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents)?,
+            _ => serde_json::from_str(&contents)?,
+        };
+        Ok(task)
+    }
+}
+
+/// Default weighting for [`Task::completion_weighted`]: more important tasks
+/// pull the rollup harder (Casual=1, Normal=2, Important=3, Critical=5).
+pub fn default_importance_weight(importance: TaskImportance) -> i32 {
+    match importance {
+        TaskImportance::Casual => 1,
+        TaskImportance::Normal => 2,
+        TaskImportance::Important => 3,
+        TaskImportance::Critical => 5,
+    }
 }
 
 /// Enum representing importance of a task.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Serialize, Deserialize)]
 pub enum TaskImportance {
     /// Lowest priority, basically just an idea.
     Casual,
@@ -295,7 +605,7 @@ pub enum TaskImportance {
 }
 
 /// Enum representing importance of a task.
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Serialize, Deserialize)]
 pub enum TaskStatus {
     /// Currently being worked on.
     InProgress,
@@ -310,12 +620,27 @@ pub enum TaskStatus {
 pub enum TaskError {
     /// Error when trying to change/read task status.
     TaskStatusError(String),
+    /// A task could not be completed because a dependency is still open.
+    /// Holds the task id and the unmet dependency id.
+    UnmetDependency(Uuid, Uuid),
+    /// A dependency edge was refused because it would form a cycle.
+    /// Holds the task id and the prospective dependency id.
+    DependencyCycle(Uuid, Uuid),
+    /// No task with the given id exists in the tree that was searched.
+    TaskNotFound(Uuid),
 }
 
 impl fmt::Display for TaskError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             TaskError::TaskStatusError(msg) => write!(f, "Invalid status: {}", msg),
+            TaskError::UnmetDependency(id, dep) => {
+                write!(f, "Task {} has an unmet dependency on {}", id, dep)
+            }
+            TaskError::DependencyCycle(id, dep) => {
+                write!(f, "Depending task {} on {} would form a cycle", id, dep)
+            }
+            TaskError::TaskNotFound(id) => write!(f, "No task with id {} was found", id),
         }
     }
 }
@@ -352,4 +677,187 @@ mod tests {
         task.add_note(String::new(), Some(40));
         assert_eq!(Completion::new(40), task.completion());
     }
+
+    #[test]
+    fn task_json_round_trip() {
+        let task = Task::new(
+            TaskBuilder::new()
+                .name("test")
+                .deadline(Local::now() + Duration::days(3))
+                .add_subtask(TaskBuilder::new().name("sub")),
+        );
+        let json = serde_json::to_string(&task).unwrap();
+        let back: Task = serde_json::from_str(&json).unwrap();
+        assert_eq!(task.id(), back.id());
+        assert_eq!(task.name(), back.name());
+        assert_eq!(task.subtasks_iter().count(), back.subtasks_iter().count());
+    }
+
+    #[test]
+    fn task_toml_round_trip_no_deadline() {
+        // Tasks without a deadline are the common case, and TOML has no
+        // null literal: the `toml` crate just omits the key, so the field
+        // must tolerate a missing key on load.
+        let task = Task::new(TaskBuilder::new().name("root"));
+        let toml_str = toml::to_string_pretty(&task).unwrap();
+        let back: Task = toml::from_str(&toml_str).unwrap();
+        assert_eq!(task.id(), back.id());
+        assert_eq!(task.name(), back.name());
+    }
+
+    #[test]
+    fn task_find_by_tag() {
+        let task = Task::new(
+            TaskBuilder::new()
+                .name("top")
+                .tag("work")
+                .add_subtask(TaskBuilder::new().name("errand").tag("errand"))
+                .add_subtask(TaskBuilder::new().name("meeting").tag("work")),
+        );
+        assert_eq!(2, task.find_by_tag("work").len());
+        assert_eq!(1, task.find_by_tag("errand").len());
+    }
+
+    #[test]
+    fn complete_blocked_by_unmet_dependency() {
+        let prereq = Task::default();
+        let mut task = Task::new(TaskBuilder::new().depends_on(prereq.id()));
+        assert!(task.complete().is_err());
+    }
+
+    #[test]
+    fn complete_failure_does_not_mutate_already_checked_siblings() {
+        // parent has subtasks [a, b]; b depends on an id that is never
+        // completed, so the whole complete() call must fail without a
+        // leaving `a` wrongly flipped to Completed.
+        let unmet_dep = Uuid::new_v4();
+        let mut parent = Task::new(
+            TaskBuilder::new()
+                .add_subtask(TaskBuilder::new().name("a"))
+                .add_subtask(TaskBuilder::new().name("b").depends_on(unmet_dep)),
+        );
+        assert!(parent.complete().is_err());
+        assert_eq!(
+            TaskStatus::InProgress,
+            parent.subtasks_iter().next().unwrap().status()
+        );
+    }
+
+    #[test]
+    fn add_dependency_rejects_cycle() {
+        let mut task = Task::default();
+        let id = task.id();
+        // a task depending on itself is the tightest cycle.
+        let graph = task.dependency_graph();
+        assert!(task.add_dependency(id, &graph).is_err());
+    }
+
+    #[test]
+    fn total_logged_sums_subtree() {
+        let mut task = Task::new(
+            TaskBuilder::new().add_subtask(TaskBuilder::new().name("sub")),
+        );
+        task.log_time(Duration::hours(1), None);
+        task.subtasks_iter_mut()
+            .next()
+            .unwrap()
+            .log_time(Duration::minutes(30), Some(String::from("poking")));
+        assert_eq!(Duration::minutes(90), task.total_logged());
+    }
+
+    #[test]
+    fn deadline_str_relative_and_strict() {
+        let relative = Task::new(TaskBuilder::new().deadline_str("in 2 days"));
+        assert!(relative.deadline().unwrap() > Local::now() + Duration::days(1));
+
+        let strict = Task::new(TaskBuilder::new().deadline_str("2021-03-20 17:30"));
+        assert_eq!(17, strict.deadline().unwrap().hour());
+
+        // a bare hour + meridiem, with no minute component, must still work.
+        let bare_hour = Task::new(TaskBuilder::new().deadline_str("tomorrow 5pm"));
+        assert_eq!(17, bare_hour.deadline().unwrap().hour());
+
+        // a nonsense string leaves no deadline set, but the error is kept
+        // around for the caller to inspect rather than only printed.
+        let bad = Task::new(TaskBuilder::new().deadline_str("whenever"));
+        assert!(bad.deadline().is_none());
+        assert!(bad.deadline_error().is_some());
+    }
+
+    #[test]
+    fn undo_restores_reset_notes() {
+        use undo::UndoableTask;
+        let mut task = Task::default();
+        let id = task.id();
+        task.add_note(String::from("did a thing"), Some(50));
+        let mut undoable = UndoableTask::new(task);
+        undoable.reset(id);
+        assert_eq!(Completion::zero(), undoable.task().completion());
+        undoable.undo(1);
+        assert_eq!(Completion::new(50), undoable.task().completion());
+    }
+
+    #[test]
+    fn completion_weighted_favours_important_subtasks() {
+        let mut task = Task::new(
+            TaskBuilder::new()
+                .add_subtask(TaskBuilder::new().importance(TaskImportance::Casual))
+                .add_subtask(TaskBuilder::new().importance(TaskImportance::Critical)),
+        );
+        // the critical subtask is done, the casual one is not.
+        task.subtasks_iter_mut()
+            .nth(1)
+            .unwrap()
+            .complete()
+            .unwrap();
+        // weighting the critical subtask more heavily beats the flat average.
+        assert!(task.completion_weighted().val() > task.completion().val());
+    }
+
+    #[test]
+    fn undoable_complete_sees_cross_branch_dependency() {
+        use undo::UndoableTask;
+        // sibling `b` depends on already-completed sibling `a`; completing
+        // `b` through the undo wrapper must see `a`'s status via the root,
+        // not just b's own (childless) subtree.
+        let mut root = Task::new(
+            TaskBuilder::new()
+                .add_subtask(TaskBuilder::new().name("a"))
+                .add_subtask(TaskBuilder::new().name("b")),
+        );
+        let a_id = root.subtasks_iter().next().unwrap().id();
+        let b_id = root.subtasks_iter().nth(1).unwrap().id();
+        let graph = root.dependency_graph();
+        root.subtasks_iter_mut()
+            .nth(1)
+            .unwrap()
+            .add_dependency(a_id, &graph)
+            .unwrap();
+
+        let mut undoable = UndoableTask::new(root);
+        undoable.complete(a_id).unwrap();
+        undoable.complete(b_id).unwrap();
+        assert_eq!(
+            TaskStatus::Completed,
+            undoable.task().subtasks_iter().nth(1).unwrap().status()
+        );
+    }
+
+    #[test]
+    fn undoable_complete_errors_on_unknown_id() {
+        use undo::UndoableTask;
+        let mut undoable = UndoableTask::new(Task::default());
+        assert!(undoable.complete(Uuid::new_v4()).is_err());
+    }
+
+    #[test]
+    fn undo_reverts_importance_change() {
+        use undo::UndoableTask;
+        let task = Task::new(TaskBuilder::new().importance(TaskImportance::Casual));
+        let id = task.id();
+        let mut undoable = UndoableTask::new(task);
+        undoable.change_importance(id, TaskImportance::Critical);
+        undoable.undo(1);
+        assert_eq!(TaskImportance::Casual, undoable.task().importance());
+    }
 }
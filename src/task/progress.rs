@@ -1,11 +1,12 @@
 //! Module containing progress notes.
 
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Duration, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::ops::{Add, AddAssign, Sub, SubAssign};
 
 /// Completion percent.
-#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct Completion(i32);
 
 impl Completion {
@@ -83,14 +84,16 @@ impl AddAssign for Completion {
 }
 
 /// Struct to represent the notes within tasks to help with progress.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProgressNote {
     /// Notes about what was completed.
     pub note: String,
     /// Timestamp of note submission.
+    #[serde(with = "super::serde_helpers::datetime")]
     pub timestamp: DateTime<Local>,
     /// Optional addition of a guess of how much of task was completed.
     /// Used to sum up completion of a task.
+    #[serde(default)]
     pub completed: Option<Completion>,
 }
 
@@ -128,3 +131,32 @@ impl ProgressNote {
         self.completed = None;
     }
 }
+
+/// A single block of effort logged against a task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeEntry {
+    /// Day the effort was logged for.
+    #[serde(with = "super::serde_helpers::naivedate")]
+    pub logged_date: NaiveDate,
+    /// Amount of time spent.
+    #[serde(with = "super::serde_helpers::duration")]
+    pub duration: Duration,
+    /// Optional note describing what the time was spent on.
+    pub note: Option<String>,
+}
+
+impl TimeEntry {
+    /// # Summary
+    /// Basic constructor, stamping the entry with today's date.
+    ///
+    /// # Parameters
+    /// * duration: amount of time spent.
+    /// * note: optional description of the effort.
+    pub fn new(duration: Duration, note: Option<String>) -> Self {
+        Self {
+            logged_date: Local::now().date_naive(),
+            duration,
+            note,
+        }
+    }
+}
@@ -0,0 +1,148 @@
+//! Serde helpers for the foreign types we store on a [`Task`](super::Task).
+//!
+//! `Uuid` and `DateTime<Local>` are serialized by hand as strings so the
+//! on-disk format stays human readable and stable across the JSON and TOML
+//! backends (RFC3339 for timestamps, hyphenated for ids).
+
+use chrono::{DateTime, Duration, Local, NaiveDate, TimeZone};
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use ::uuid::Uuid;
+
+/// (De)serialize a [`Uuid`] as its hyphenated string form.
+pub mod uuid {
+    use super::*;
+
+    pub fn serialize<S>(id: &Uuid, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&id.to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Uuid, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        Uuid::parse_str(&s).map_err(D::Error::custom)
+    }
+}
+
+/// (De)serialize a `HashSet<Uuid>` as a list of hyphenated strings.
+pub mod uuid_set {
+    use super::*;
+    use std::collections::HashSet;
+
+    pub fn serialize<S>(ids: &HashSet<Uuid>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let strings: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+        strings.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<HashSet<Uuid>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let strings = Vec::<String>::deserialize(deserializer)?;
+        strings
+            .iter()
+            .map(|s| Uuid::parse_str(s).map_err(D::Error::custom))
+            .collect()
+    }
+}
+
+/// (De)serialize a [`NaiveDate`] as a `%F` (YYYY-MM-DD) string.
+pub mod naivedate {
+    use super::*;
+
+    pub fn serialize<S>(date: &NaiveDate, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&date.format("%F").to_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        NaiveDate::parse_from_str(&s, "%F").map_err(D::Error::custom)
+    }
+}
+
+/// (De)serialize a [`Duration`] as a whole number of seconds.
+pub mod duration {
+    use super::*;
+
+    pub fn serialize<S>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_i64(duration.num_seconds())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let secs = i64::deserialize(deserializer)?;
+        Ok(Duration::seconds(secs))
+    }
+}
+
+/// (De)serialize a [`DateTime<Local>`] as an RFC3339 string.
+pub mod datetime {
+    use super::*;
+
+    pub fn serialize<S>(dt: &DateTime<Local>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&dt.to_rfc3339())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<DateTime<Local>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        let parsed = DateTime::parse_from_rfc3339(&s).map_err(D::Error::custom)?;
+        Ok(Local.from_utc_datetime(&parsed.naive_utc()))
+    }
+
+    /// Variant for `Option<DateTime<Local>>` fields.
+    pub mod option {
+        use super::*;
+
+        pub fn serialize<S>(
+            dt: &Option<DateTime<Local>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match dt {
+                Some(dt) => serializer.serialize_some(&dt.to_rfc3339()),
+                None => serializer.serialize_none(),
+            }
+        }
+
+        pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Local>>, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let opt = Option::<String>::deserialize(deserializer)?;
+            match opt {
+                Some(s) => {
+                    let parsed = DateTime::parse_from_rfc3339(&s).map_err(D::Error::custom)?;
+                    Ok(Some(Local.from_utc_datetime(&parsed.naive_utc())))
+                }
+                None => Ok(None),
+            }
+        }
+    }
+}
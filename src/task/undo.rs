@@ -0,0 +1,183 @@
+//! Multi-step undo for destructive task mutations.
+//!
+//! [`UndoableTask`] wraps a task tree and mirrors the mutating methods of
+//! [`Task`], recording the inverse of each change in a history stack so that
+//! [`UndoableTask::undo`] can roll them back in reverse order.
+
+use chrono::{DateTime, Local};
+use std::error::Error;
+use uuid::Uuid;
+
+use super::{Task, TaskImportance};
+
+/// The inverse of a single task mutation, keyed by the affected task id.
+#[derive(Debug)]
+pub enum TaskEdit {
+    /// Restore a task's importance to its previous value.
+    Importance {
+        /// Id of the affected task.
+        id: Uuid,
+        /// Importance to restore.
+        prev: TaskImportance,
+    },
+    /// Restore a task's deadline to its previous value (`None` removes it).
+    Deadline {
+        /// Id of the affected task.
+        id: Uuid,
+        /// Deadline to restore.
+        prev: Option<DateTime<Local>>,
+    },
+    /// Remove the note appended by the recorded `add_note` call.
+    Note {
+        /// Id of the affected task.
+        id: Uuid,
+    },
+    /// Restore a whole subtree that was reset or completed.
+    Snapshot {
+        /// Id of the affected task.
+        id: Uuid,
+        /// Cloned subtree captured before the mutation.
+        prev: Box<Task>,
+    },
+}
+
+/// A task tree paired with an undo history.
+#[derive(Debug)]
+pub struct UndoableTask {
+    root: Task,
+    history: Vec<TaskEdit>,
+}
+
+impl UndoableTask {
+    /// Wrap a task tree with an empty history.
+    pub fn new(root: Task) -> Self {
+        Self {
+            root,
+            history: Vec::new(),
+        }
+    }
+
+    /// Borrow the wrapped task tree.
+    pub fn task(&self) -> &Task {
+        &self.root
+    }
+
+    /// Unwrap back into the bare task tree, dropping the history.
+    pub fn into_inner(self) -> Task {
+        self.root
+    }
+
+    /// Number of undoable edits currently on the stack.
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Change the importance of the task `id`, recording the inverse.
+    pub fn change_importance(&mut self, id: Uuid, new: TaskImportance) -> Option<TaskImportance> {
+        let prev = self.root.find_task_mut(id)?.change_importance(new);
+        if let Some(prev) = prev {
+            self.history.push(TaskEdit::Importance { id, prev });
+        }
+        prev
+    }
+
+    /// Change the deadline of the task `id`, recording the inverse.
+    pub fn change_deadline(
+        &mut self,
+        id: Uuid,
+        deadline: DateTime<Local>,
+    ) -> Option<DateTime<Local>> {
+        let task = self.root.find_task_mut(id)?;
+        let prev = task.change_deadline(deadline);
+        self.history.push(TaskEdit::Deadline { id, prev });
+        prev
+    }
+
+    /// Remove the deadline of the task `id`, recording the inverse.
+    pub fn remove_deadline(&mut self, id: Uuid) -> Option<DateTime<Local>> {
+        let task = self.root.find_task_mut(id)?;
+        let prev = task.remove_deadline();
+        if prev.is_some() {
+            self.history.push(TaskEdit::Deadline { id, prev });
+        }
+        prev
+    }
+
+    /// Add a note to the task `id`, recording the inverse.
+    pub fn add_note(&mut self, id: Uuid, note: String, perc: Option<i32>) {
+        if let Some(task) = self.root.find_task_mut(id) {
+            task.add_note(note, perc);
+            self.history.push(TaskEdit::Note { id });
+        }
+    }
+
+    /// Reset the task `id`, snapshotting the subtree so notes can be restored.
+    pub fn reset(&mut self, id: Uuid) {
+        if let Some(task) = self.root.find_task_mut(id) {
+            let snapshot = Box::new(task.clone());
+            task.reset();
+            self.history.push(TaskEdit::Snapshot { id, prev: snapshot });
+        }
+    }
+
+    /// Complete the task `id`, snapshotting the subtree for undo.
+    ///
+    /// Routed through the wrapped root rather than calling `Task::complete`
+    /// on the found subtask directly, since dependencies may point at a
+    /// sibling branch that only the root's status snapshot can see.
+    pub fn complete(&mut self, id: Uuid) -> Result<(), Box<dyn Error>> {
+        let snapshot = match self.root.find_task_mut(id) {
+            Some(task) => Box::new(task.clone()),
+            None => return Err(Box::new(super::TaskError::TaskNotFound(id))),
+        };
+        let result = self
+            .root
+            .complete_task(id)
+            .expect("task was just found above");
+        result?;
+        self.history.push(TaskEdit::Snapshot { id, prev: snapshot });
+        Ok(())
+    }
+
+    /// Undo the last `n` recorded edits, most recent first.
+    pub fn undo(&mut self, n: usize) {
+        for _ in 0..n {
+            match self.history.pop() {
+                Some(edit) => self.apply_inverse(edit),
+                None => break,
+            }
+        }
+    }
+
+    fn apply_inverse(&mut self, edit: TaskEdit) {
+        match edit {
+            TaskEdit::Importance { id, prev } => {
+                if let Some(task) = self.root.find_task_mut(id) {
+                    task.change_importance(prev);
+                }
+            }
+            TaskEdit::Deadline { id, prev } => {
+                if let Some(task) = self.root.find_task_mut(id) {
+                    match prev {
+                        Some(deadline) => {
+                            task.change_deadline(deadline);
+                        }
+                        None => {
+                            task.remove_deadline();
+                        }
+                    }
+                }
+            }
+            TaskEdit::Note { id } => {
+                if let Some(task) = self.root.find_task_mut(id) {
+                    task.remove_last_note();
+                }
+            }
+            TaskEdit::Snapshot { id, prev } => {
+                if let Some(task) = self.root.find_task_mut(id) {
+                    *task = *prev;
+                }
+            }
+        }
+    }
+}